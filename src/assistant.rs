@@ -0,0 +1,69 @@
+use std::fmt;
+
+use crate::llm::{ChatMessage, LlmClient, LlmError};
+use crate::tools::{self, ToolCache};
+
+/// Upper bound on model <-> tool round-trips for a single `/ask`, so a model
+/// that keeps requesting tools can't loop forever.
+const MAX_STEPS: usize = 8;
+
+const SYSTEM_PROMPT: &str = "You are a Compiler Explorer assistant embedded in a Telegram bot. \
+Answer the user's request by calling the available tools to compile or run code on godbolt.org. \
+Keep your final answer concise and suitable for a chat message.";
+
+#[derive(Debug)]
+pub enum AssistantError {
+    Llm(LlmError),
+    TooManySteps,
+}
+
+impl fmt::Display for AssistantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssistantError::Llm(e) => write!(f, "{e}"),
+            AssistantError::TooManySteps => {
+                write!(f, "gave up after too many tool-calling steps without a final answer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssistantError {}
+
+impl From<LlmError> for AssistantError {
+    fn from(e: LlmError) -> Self {
+        AssistantError::Llm(e)
+    }
+}
+
+/// Answers a natural-language `prompt` by driving the godbolt tools through
+/// the model's function-calling loop until it returns a final message.
+pub async fn ask(prompt: &str) -> Result<String, AssistantError> {
+    let client = LlmClient::from_env()?;
+    let tool_definitions = tools::tool_definitions();
+    let mut cache = ToolCache::default();
+
+    let mut messages = vec![ChatMessage::system(SYSTEM_PROMPT), ChatMessage::user(prompt)];
+
+    for _ in 0..MAX_STEPS {
+        let choice = client.chat_completion(&messages, &tool_definitions).await?;
+
+        let Some(tool_calls) = choice.tool_calls else {
+            return Ok(choice.content.unwrap_or_default());
+        };
+
+        messages.push(ChatMessage::assistant_tool_calls(tool_calls.clone()));
+
+        for call in tool_calls {
+            log::info!("Assistant invoking tool '{}' with {}", call.function.name, call.function.arguments);
+            let result = tools::dispatch(&call.function.name, &call.function.arguments, &mut cache).await;
+            let content = match result {
+                Ok(json) => json,
+                Err(e) => format!("error: {e}"),
+            };
+            messages.push(ChatMessage::tool_result(call.id, content));
+        }
+    }
+
+    Err(AssistantError::TooManySteps)
+}
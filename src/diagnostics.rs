@@ -0,0 +1,260 @@
+use serde::Serialize;
+
+/// Severity of a single compiler diagnostic, as reported by GCC/Clang/rustc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A single diagnostic parsed out of a compiler's stderr, mirroring how
+/// LSP/editor tooling models diagnostics.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub message: String,
+}
+
+/// Parses a compiler's stderr into a list of diagnostics. Understands two
+/// header shapes:
+/// - GCC/Clang's single-line `path:line:col: error|warning|note: message`,
+///   where a missing `col` is handled, multi-line messages are folded into
+///   the preceding header's message until the next header line, and notes
+///   attached to a preceding error simply appear as their own `Diagnostic`
+///   immediately after it.
+/// - rustc's default two-line header, e.g. `error[E0425]: message` followed
+///   by a ` --> file:line:col` location line.
+pub fn parse(stderr: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut awaiting_location: Option<usize> = None;
+    let mut in_rustc_diagnostic = false;
+
+    for line in stderr.lines() {
+        if let Some((file, line_no, column, severity, message)) = parse_header(line) {
+            diagnostics.push(Diagnostic {
+                severity,
+                file,
+                line: line_no,
+                column,
+                message,
+            });
+            awaiting_location = None;
+            in_rustc_diagnostic = false;
+            continue;
+        }
+
+        if let Some((severity, message)) = parse_rustc_header(line) {
+            diagnostics.push(Diagnostic {
+                severity,
+                file: None,
+                line: 0,
+                column: None,
+                message,
+            });
+            awaiting_location = Some(diagnostics.len() - 1);
+            in_rustc_diagnostic = true;
+            continue;
+        }
+
+        if let Some((file, line_no, column)) = parse_rustc_location(line) {
+            if let Some(index) = awaiting_location.take() {
+                let diagnostic = &mut diagnostics[index];
+                diagnostic.file = file;
+                diagnostic.line = line_no;
+                diagnostic.column = column;
+            }
+            continue;
+        }
+
+        // Only rustc's own `-->`/`|` decoration gets dropped here; GCC/Clang's
+        // `N | <source>` caret snippets after a `parse_header` line are real
+        // diagnostic content and fall through to the message-folding below.
+        if in_rustc_diagnostic && is_rustc_gutter_line(line) {
+            continue;
+        }
+
+        if let Some(last) = diagnostics.last_mut() {
+            if !line.trim().is_empty() {
+                last.message.push('\n');
+                last.message.push_str(line);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn parse_header(line: &str) -> Option<(Option<String>, u32, Option<u32>, Severity, String)> {
+    let colon_idx = line.find(':')?;
+    let file = &line[..colon_idx];
+    let rest = &line[colon_idx + 1..];
+
+    let (line_no, rest) = take_leading_number(rest)?;
+    let rest = rest.strip_prefix(':')?;
+
+    let (column, rest) = match take_leading_number(rest) {
+        Some((num, after)) => (Some(num), after.strip_prefix(':')?),
+        None => (None, rest),
+    };
+
+    let trimmed = rest.trim_start();
+    let (severity, rest) = if let Some(rest) = trimmed.strip_prefix("error") {
+        (Severity::Error, rest)
+    } else if let Some(rest) = trimmed.strip_prefix("warning") {
+        (Severity::Warning, rest)
+    } else if let Some(rest) = trimmed.strip_prefix("note") {
+        (Severity::Note, rest)
+    } else {
+        return None;
+    };
+
+    let message = rest.strip_prefix(':')?.trim_start().to_string();
+    let file = if file.is_empty() { None } else { Some(file.to_string()) };
+
+    Some((file, line_no, column, severity, message))
+}
+
+/// Parses rustc's primary diagnostic line, e.g. `error[E0425]: message`,
+/// `error: message` or `warning: message`. Never carries a location itself;
+/// that comes from a following [`parse_rustc_location`] line.
+fn parse_rustc_header(line: &str) -> Option<(Severity, String)> {
+    let (severity, mut rest) = if let Some(rest) = line.strip_prefix("error") {
+        (Severity::Error, rest)
+    } else if let Some(rest) = line.strip_prefix("warning") {
+        (Severity::Warning, rest)
+    } else if let Some(rest) = line.strip_prefix("note") {
+        (Severity::Note, rest)
+    } else {
+        return None;
+    };
+
+    if let Some(after_bracket) = rest.strip_prefix('[') {
+        let end = after_bracket.find(']')?;
+        rest = &after_bracket[end + 1..];
+    }
+
+    let message = rest.strip_prefix(": ")?.trim().to_string();
+    Some((severity, message))
+}
+
+/// Parses rustc's ` --> file:line:col` (or `file:line` without a column)
+/// location line that follows a [`parse_rustc_header`] line.
+fn parse_rustc_location(line: &str) -> Option<(Option<String>, u32, Option<u32>)> {
+    let rest = line.trim_start().strip_prefix("--> ")?;
+    let segments: Vec<&str> = rest.rsplitn(3, ':').collect();
+
+    match segments.as_slice() {
+        [column, line_no, file] => {
+            let column = column.trim().parse::<u32>().ok();
+            let line_no = line_no.trim().parse::<u32>().ok()?;
+            let file = if file.is_empty() { None } else { Some(file.to_string()) };
+            Some((file, line_no, column))
+        }
+        [line_no, file] => {
+            let line_no = line_no.trim().parse::<u32>().ok()?;
+            let file = if file.is_empty() { None } else { Some(file.to_string()) };
+            Some((file, line_no, None))
+        }
+        _ => None,
+    }
+}
+
+/// rustc pads source snippets under a header with a `|` gutter, e.g.
+/// `  |` or `2 |     x`. These lines just echo source we already have, so
+/// they're dropped rather than folded into the diagnostic's message.
+fn is_rustc_gutter_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('|') {
+        return true;
+    }
+
+    let mut parts = trimmed.splitn(2, char::is_whitespace);
+    let Some(first) = parts.next() else {
+        return false;
+    };
+    if first.is_empty() || !first.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    parts.next().is_some_and(|rest| rest.trim_start().starts_with('|'))
+}
+
+fn take_leading_number(s: &str) -> Option<(u32, &str)> {
+    let digits_len = s.chars().take_while(char::is_ascii_digit).count();
+    if digits_len == 0 {
+        return None;
+    }
+    let (digits, rest) = s.split_at(digits_len);
+    digits.parse::<u32>().ok().map(|n| (n, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gcc_header_with_column() {
+        let diags = parse("main.c:3:5: error: expected ';' before '}' token");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Severity::Error);
+        assert_eq!(diags[0].file.as_deref(), Some("main.c"));
+        assert_eq!(diags[0].line, 3);
+        assert_eq!(diags[0].column, Some(5));
+        assert_eq!(diags[0].message, "expected ';' before '}' token");
+    }
+
+    #[test]
+    fn parses_gcc_header_without_column() {
+        let diags = parse("main.c:3: error: missing semicolon");
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].column, None);
+        assert_eq!(diags[0].line, 3);
+        assert_eq!(diags[0].message, "missing semicolon");
+    }
+
+    #[test]
+    fn folds_multi_line_messages_into_preceding_header() {
+        let diags = parse(
+            "main.c:3:5: error: expected ';' before '}' token\n    3 | int x\n      |       ^",
+        );
+        assert_eq!(diags.len(), 1);
+        assert!(diags[0].message.contains("expected ';' before '}' token"));
+        assert!(diags[0].message.contains("int x"));
+    }
+
+    #[test]
+    fn keeps_note_attached_to_preceding_error_as_its_own_diagnostic() {
+        let diags = parse(
+            "main.c:3:5: error: 'x' was not declared in this scope\nmain.c:1:1: note: suggested alternative",
+        );
+        assert_eq!(diags.len(), 2);
+        assert_eq!(diags[0].severity, Severity::Error);
+        assert_eq!(diags[1].severity, Severity::Note);
+        assert_eq!(diags[1].line, 1);
+    }
+
+    #[test]
+    fn parses_rustc_two_line_diagnostic() {
+        let diags = parse(
+            "error[E0425]: cannot find value `x` in this scope\n --> src/main.rs:2:5\n  |\n2 |     x\n  |     ^ not found in this scope",
+        );
+        assert!(!diags.is_empty());
+        assert_eq!(diags[0].severity, Severity::Error);
+        assert_eq!(diags[0].file.as_deref(), Some("src/main.rs"));
+        assert_eq!(diags[0].line, 2);
+        assert_eq!(diags[0].column, Some(5));
+        assert!(diags[0].message.contains("cannot find value"));
+    }
+
+    #[test]
+    fn parses_rustc_warning_without_error_code() {
+        let diags = parse("warning: unused variable: `y`\n --> src/main.rs:3:9\n  |\n3 |     let y = 1;");
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(diags[0].file.as_deref(), Some("src/main.rs"));
+        assert_eq!(diags[0].line, 3);
+        assert_eq!(diags[0].column, Some(9));
+    }
+}
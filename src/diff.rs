@@ -0,0 +1,87 @@
+/// Upper bound on the number of lines (on either side) `line_diff` will run
+/// its `O(n*m)` LCS table over. Above this, the inputs are diffed verbatim
+/// without alignment rather than allocating an unbounded DP table.
+pub const MAX_DIFF_LINES: usize = 2000;
+
+/// Computes a line-level diff between `old` and `new`, returning lines
+/// prefixed with `+`, `-`, or two spaces for unchanged lines. If either side
+/// exceeds [`MAX_DIFF_LINES`], falls back to a plain `-old`/`+new` dump with
+/// no line-level alignment, since the full LCS table would be unbounded.
+pub fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let n = old_lines.len();
+    let m = new_lines.len();
+
+    if n > MAX_DIFF_LINES || m > MAX_DIFF_LINES {
+        return old_lines
+            .iter()
+            .map(|line| format!("- {line}"))
+            .chain(new_lines.iter().map(|line| format!("+ {line}")))
+            .collect();
+    }
+
+    let mut lcs_len = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs_len[i][j] = if old_lines[i] == new_lines[j] {
+                lcs_len[i + 1][j + 1] + 1
+            } else {
+                lcs_len[i + 1][j].max(lcs_len[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            result.push(format!("  {}", old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if lcs_len[i + 1][j] >= lcs_len[i][j + 1] {
+            result.push(format!("- {}", old_lines[i]));
+            i += 1;
+        } else {
+            result.push(format!("+ {}", new_lines[j]));
+            j += 1;
+        }
+    }
+    while i < n {
+        result.push(format!("- {}", old_lines[i]));
+        i += 1;
+    }
+    while j < m {
+        result.push(format!("+ {}", new_lines[j]));
+        j += 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_inputs_are_all_unchanged() {
+        let diff = line_diff("a\nb\nc", "a\nb\nc");
+        assert_eq!(diff, vec!["  a", "  b", "  c"]);
+    }
+
+    #[test]
+    fn detects_insertions_and_deletions() {
+        let diff = line_diff("a\nb\nc", "a\nc\nd");
+        assert_eq!(diff, vec!["  a", "- b", "  c", "+ d"]);
+    }
+
+    #[test]
+    fn falls_back_to_plain_dump_past_the_size_cap() {
+        let old = "x\n".repeat(MAX_DIFF_LINES + 1);
+        let new = "y\n".repeat(2);
+        let diff = line_diff(&old, &new);
+        assert_eq!(diff.len(), MAX_DIFF_LINES + 1 + 2);
+        assert!(diff.iter().take(MAX_DIFF_LINES + 1).all(|line| line == "- x"));
+        assert!(diff.iter().skip(MAX_DIFF_LINES + 1).all(|line| line == "+ y"));
+    }
+}
@@ -1,7 +1,9 @@
 use reqwest::Error;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+use crate::diagnostics::{self, Diagnostic};
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Language {
     pub id: String,
     pub name: String,
@@ -10,15 +12,63 @@ pub struct Language {
 #[derive(Debug, Serialize)]
 struct CompileRequest<'a> {
     source: &'a str,
-    options: CompileOptions,
+    options: &'a CompileOptions,
 }
 
-#[derive(Debug, Serialize)]
-struct CompileOptions {}
+/// A library version to link against, as godbolt identifies them (e.g.
+/// `{ id: "fmt", version: "trunk" }`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Library {
+    pub id: String,
+    pub version: String,
+}
+
+/// Toggles godbolt applies to the returned assembly. `labels` also controls
+/// whether each [`AsmLine`] carries the source line it was generated from.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompileFilters {
+    pub demangle: bool,
+    pub directives: bool,
+    pub comments: bool,
+    pub labels: bool,
+    pub intel: bool,
+    pub execute: bool,
+}
+
+impl Default for CompileFilters {
+    fn default() -> Self {
+        Self {
+            demangle: true,
+            directives: true,
+            comments: true,
+            labels: true,
+            intel: true,
+            execute: false,
+        }
+    }
+}
+
+/// User-controllable compilation options: extra compiler flags, libraries to
+/// link, and output filters. Threaded through both `compile` and `execute`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CompileOptions {
+    #[serde(rename = "userArguments", default)]
+    pub user_arguments: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub libraries: Vec<Library>,
+    #[serde(default)]
+    pub filters: CompileFilters,
+}
+
+#[derive(Debug, Deserialize)]
+struct AsmLineSource {
+    line: Option<u32>,
+}
 
 #[derive(Debug, Deserialize)]
-struct AsmLine {
+struct RawAsmLine {
     text: String,
+    source: Option<AsmLineSource>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -28,30 +78,33 @@ struct StderrLine {
 
 #[derive(Debug, Deserialize)]
 struct CompileResponse {
-    asm: Vec<AsmLine>,
+    asm: Vec<RawAsmLine>,
     stderr: Vec<StderrLine>,
     #[allow(dead_code)]
     code: i32,
 }
 
-#[derive(Debug)]
+/// A single line of assembly, with the source line it was generated from
+/// when godbolt's `labels` filter is enabled (see [`CompileFilters`]).
+#[derive(Debug, Serialize, Clone)]
+pub struct AsmLine {
+    pub text: String,
+    pub source_line: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
 pub enum CompilationOutput {
-    Assembly(String),
-    Stderr(String),
+    Assembly(Vec<AsmLine>),
+    Diagnostics(Vec<Diagnostic>),
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Compiler {
     pub id: String,
     pub name: String,
     pub semver: String,
 }
 
-#[derive(Debug, Serialize)]
-struct ExecuteFilterOptions {
-    execute: bool,
-}
-
 #[derive(Debug, Serialize)]
 struct ExecuteParameters<'a> {
     stdin: &'a str,
@@ -59,7 +112,11 @@ struct ExecuteParameters<'a> {
 
 #[derive(Debug, Serialize)]
 struct ExecuteOptions<'a> {
-    filters: ExecuteFilterOptions,
+    #[serde(rename = "userArguments")]
+    user_arguments: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    libraries: &'a [Library],
+    filters: CompileFilters,
     #[serde(rename = "executeParameters")]
     execute_parameters: ExecuteParameters<'a>,
 }
@@ -98,7 +155,7 @@ struct ExecuteResponse {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum ExecutionOutput {
     BuildFailure(String),
     ExecutionSuccess {
@@ -121,20 +178,25 @@ fn route(path: &str) -> String {
 /// # Arguments
 /// * `compiler_id` - The ID of the compiler (e.g., "g122" for GCC 12.2).
 /// * `code` - The source code to compile.
+/// * `options` - User arguments, libraries and filters to pass to godbolt.
 ///
 /// # Returns
 /// A `Result` which is:
 /// * `Ok(CompilationOutput)` on a successful API call. The enum will contain
-///   either the assembly or the compiler's stderr.
+///   either the assembly or the compiler's diagnostics.
 /// * `Err(reqwest::Error)` if a network or deserialization error occurs.
-pub async fn compile(compiler_id: &str, code: &str) -> Result<CompilationOutput, Error> {
+pub async fn compile(
+    compiler_id: &str,
+    code: &str,
+    options: &CompileOptions,
+) -> Result<CompilationOutput, Error> {
     log::info!("Received '{code}' to compile with {compiler_id}.");
 
     let request_url = route(&format!("compiler/{compiler_id}/compile"));
 
     let request_body = CompileRequest {
         source: code,
-        options: CompileOptions {},
+        options,
     };
 
     let client = reqwest::Client::new();
@@ -155,14 +217,17 @@ pub async fn compile(compiler_id: &str, code: &str) -> Result<CompilationOutput,
             .map(|line| line.text)
             .collect::<Vec<String>>()
             .join("\n");
-        Ok(CompilationOutput::Stderr(error_output))
+        let error_output = strip_ansi_escapes::strip_str(&error_output);
+        Ok(CompilationOutput::Diagnostics(diagnostics::parse(&error_output)))
     } else {
         let assembly_output = compile_res
             .asm
             .into_iter()
-            .map(|line| line.text)
-            .collect::<Vec<String>>()
-            .join("\n");
+            .map(|line| AsmLine {
+                text: line.text,
+                source_line: line.source.and_then(|source| source.line),
+            })
+            .collect::<Vec<AsmLine>>();
         Ok(CompilationOutput::Assembly(assembly_output))
     }
 }
@@ -178,15 +243,21 @@ pub async fn execute(
     compiler_id: &str,
     code: &str,
     stdin: &str,
+    options: &CompileOptions,
 ) -> Result<ExecutionOutput, Error> {
     log::info!("Executing '{code}' with compiler '{compiler_id}' and stdin '{stdin}'");
 
     let request_url = route(&format!("compiler/{compiler_id}/compile"));
 
+    let mut filters = options.filters.clone();
+    filters.execute = true; // This is the key
+
     let request_body = ExecuteRequest {
         source: code,
         options: ExecuteOptions {
-            filters: ExecuteFilterOptions { execute: true }, // This is the key
+            user_arguments: &options.user_arguments,
+            libraries: &options.libraries,
+            filters,
             execute_parameters: ExecuteParameters { stdin },
         },
     };
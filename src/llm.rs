@@ -0,0 +1,244 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single message in a chat-completion conversation, following the
+/// `role`/`content`/`tool_calls` shape used by OpenAI-compatible APIs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: "system".to_string(),
+            content: Some(content.into()),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: Some(content.into()),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    pub fn assistant_tool_calls(tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: None,
+            tool_calls: Some(tool_calls),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn tool_result(tool_call_id: String, content: String) -> Self {
+        Self {
+            role: "tool".to_string(),
+            content: Some(content),
+            tool_calls: None,
+            tool_call_id: Some(tool_call_id),
+        }
+    }
+}
+
+/// A request from the model to invoke one of the tools we advertised.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "default_tool_call_type")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+fn default_tool_call_type() -> String {
+    "function".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments, as returned by the model.
+    pub arguments: String,
+}
+
+/// Describes a callable tool to the model, mirroring the JSON-schema based
+/// function-calling format shared by OpenAI-compatible chat-completion APIs.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDefinition {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: ToolFunctionDef,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolFunctionDef {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+impl ToolDefinition {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            kind: "function".to_string(),
+            function: ToolFunctionDef {
+                name: name.into(),
+                description: description.into(),
+                parameters,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tools: &'a [ToolDefinition],
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatCompletionChoiceEnvelope>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChoiceEnvelope {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorResponse {
+    error: ApiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    message: String,
+}
+
+/// The model's reply to a chat-completion call: either final content, or a
+/// set of tool calls it wants the caller to resolve before it continues.
+#[derive(Debug)]
+pub struct ChatCompletionChoice {
+    pub content: Option<String>,
+    pub tool_calls: Option<Vec<ToolCall>>,
+}
+
+#[derive(Debug)]
+pub enum LlmError {
+    MissingConfig(&'static str),
+    Request(reqwest::Error),
+    Api(String),
+    ToolCallingUnsupported(String),
+}
+
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LlmError::MissingConfig(var) => write!(f, "missing required environment variable {var}"),
+            LlmError::Request(e) => write!(f, "request to LLM API failed: {e}"),
+            LlmError::Api(msg) => write!(f, "LLM API returned an error: {msg}"),
+            LlmError::ToolCallingUnsupported(model) => {
+                write!(f, "configured model '{model}' does not support tool calling")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LlmError {}
+
+impl From<reqwest::Error> for LlmError {
+    fn from(e: reqwest::Error) -> Self {
+        LlmError::Request(e)
+    }
+}
+
+/// A thin client for an OpenAI-compatible chat-completion endpoint, used to
+/// drive the `/ask` natural-language command.
+pub struct LlmClient {
+    api_key: String,
+    base_url: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl LlmClient {
+    /// Builds a client from `LLM_API_KEY`, `LLM_MODEL` and the optional
+    /// `LLM_BASE_URL` (defaults to the OpenAI API) environment variables.
+    pub fn from_env() -> Result<Self, LlmError> {
+        let api_key = std::env::var("LLM_API_KEY").map_err(|_| LlmError::MissingConfig("LLM_API_KEY"))?;
+        let model = std::env::var("LLM_MODEL").map_err(|_| LlmError::MissingConfig("LLM_MODEL"))?;
+        let base_url = std::env::var("LLM_BASE_URL")
+            .unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
+
+        Ok(Self {
+            api_key,
+            base_url,
+            model,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    pub async fn chat_completion(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolDefinition],
+    ) -> Result<ChatCompletionChoice, LlmError> {
+        let request_body = ChatCompletionRequest {
+            model: &self.model,
+            messages,
+            tools,
+        };
+
+        let res = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&request_body)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            let body = res.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<ApiErrorResponse>(&body)
+                .map(|e| e.error.message)
+                .unwrap_or(body);
+
+            if !tools.is_empty() && mentions_tool_calling_support(&message) {
+                return Err(LlmError::ToolCallingUnsupported(self.model.clone()));
+            }
+            return Err(LlmError::Api(message));
+        }
+
+        let mut response: ChatCompletionResponse = res.json().await?;
+        let choice = response
+            .choices
+            .pop()
+            .ok_or_else(|| LlmError::Api("response contained no choices".to_string()))?;
+
+        Ok(ChatCompletionChoice {
+            content: choice.message.content,
+            tool_calls: choice.message.tool_calls,
+        })
+    }
+}
+
+fn mentions_tool_calling_support(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    (lower.contains("tool") || lower.contains("function")) && lower.contains("support")
+}
@@ -1,22 +1,68 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::future::join_all;
 use teloxide::{
+    dispatching::UpdateFilterExt,
     payloads::SendMessage,
     prelude::*,
     requests::JsonRequest,
-    types::{MessageEntityKind, ParseMode},
+    types::{MessageEntityKind, ParseMode, Update},
     utils::{command::BotCommands, markdown},
 };
 
+mod assistant;
+mod diagnostics;
+mod diff;
 mod godbolt;
+mod llm;
+mod rpc;
+mod tools;
+
+/// Per-chat default flags set with `/setflags`, shared across handler
+/// invocations for the lifetime of the bot process.
+type ChatFlags = Arc<Mutex<HashMap<ChatId, String>>>;
 
 #[tokio::main]
 async fn main() {
     pretty_env_logger::init();
+
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.iter().any(|a| a == "--serve") {
+        log::info!("Starting JSON-RPC server over stdio...");
+        rpc::serve_stdio().await;
+        return;
+    }
+
+    if let Some(pos) = args.iter().position(|a| a == "--serve-tcp") {
+        let addr = args
+            .get(pos + 1)
+            .cloned()
+            .unwrap_or_else(|| "127.0.0.1:9257".to_string());
+        log::info!("Starting JSON-RPC server on {addr}...");
+        if let Err(e) = rpc::serve_tcp(&addr).await {
+            log::error!("JSON-RPC server error: {e}");
+        }
+        return;
+    }
+
     log::info!("Starting command bot...");
 
     let bot = Bot::from_env();
-
-    Command::repl(bot, answer).await;
+    let chat_flags: ChatFlags = Arc::new(Mutex::new(HashMap::new()));
+
+    let handler = Update::filter_message()
+        .filter_command::<Command>()
+        .endpoint(answer);
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![chat_flags])
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
 }
 
 #[derive(BotCommands, Clone)]
@@ -29,12 +75,18 @@ enum Command {
     Help,
     #[command(description = "pong.")]
     Ping,
-    #[command(description = "compile the code from the message.", aliases = ["c"])]
+    #[command(description = "compile the code from the message, pass --annotate to group assembly by source line.", aliases = ["c"])]
     Compile,
     #[command(description = "list all supported languages.", aliases = ["ls"])]
     Languages,
     #[command(description = "list all supported compilers, specific language id can be specified.")]
     Compilers { language: String },
+    #[command(description = "ask the assistant to compile/run code on your behalf in plain language.")]
+    Ask { prompt: String },
+    #[command(description = "set default compiler flags for this chat, e.g. '-O3 -std=c++20'.")]
+    SetFlags { flags: String },
+    #[command(description = "compile the code with two or more compilers and diff their assembly.", aliases = ["cmp"])]
+    Diff,
 }
 
 fn format_languages(langs: &[godbolt::Language]) -> String {
@@ -85,6 +137,15 @@ fn wrap_in_md(s: &str) -> String {
     format!("```\n{safe_s}\n```")
 }
 
+/// Like [`wrap_in_md`], but for bodies where leading `+`/`-` prefixes must
+/// survive verbatim (e.g. a diff). MarkdownV2 code spans only need `` ` ``
+/// and `\` escaped, unlike plain text, so `markdown::escape` would turn
+/// every `+`/`-` into a literal backslash-escaped character.
+fn wrap_in_diff_md(s: &str) -> String {
+    let safe_s = markdown::escape_code(s);
+    format!("```\n{safe_s}\n```")
+}
+
 fn trim_message(s: &str) -> Cow<str> {
     const TELEGRAM_MAX_MSG_LEN: usize = 4096;
     const TRUNCATION_SUFFIX_PLAIN: &str = "\n... (message trimmed)";
@@ -182,6 +243,65 @@ fn format_compilers(compilers: &[&godbolt::Compiler]) -> String {
     wrap_in_md(&output_lines.join("\n"))
 }
 
+fn format_diagnostics(diags: &[diagnostics::Diagnostic], code: &str) -> String {
+    let source_lines: Vec<&str> = code.lines().collect();
+
+    let reports = diags
+        .iter()
+        .map(|diag| {
+            let severity = match diag.severity {
+                diagnostics::Severity::Error => "error",
+                diagnostics::Severity::Warning => "warning",
+                diagnostics::Severity::Note => "note",
+            };
+            let location = match (&diag.file, diag.column) {
+                (Some(file), Some(col)) => format!("{file}:{}:{col}", diag.line),
+                (Some(file), None) => format!("{file}:{}", diag.line),
+                (None, Some(col)) => format!("{}:{col}", diag.line),
+                (None, None) => format!("{}", diag.line),
+            };
+
+            let mut report = format!("{location}: {severity}: {}", diag.message);
+
+            if let Some(source_line) = source_lines.get((diag.line as usize).wrapping_sub(1)) {
+                report.push('\n');
+                report.push_str(source_line);
+                if let Some(col) = diag.column {
+                    report.push('\n');
+                    report.push_str(&" ".repeat((col as usize).saturating_sub(1)));
+                    report.push('^');
+                }
+            }
+
+            report
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    wrap_in_md(&reports)
+}
+
+/// Groups assembly instructions under headers showing the source line they
+/// were generated from, for the `/compile --annotate` rendering mode.
+fn format_annotated_assembly(lines: &[godbolt::AsmLine]) -> String {
+    let mut output = String::new();
+    let mut current_source_line: Option<Option<u32>> = None;
+
+    for line in lines {
+        if current_source_line != Some(line.source_line) {
+            current_source_line = Some(line.source_line);
+            match line.source_line {
+                Some(n) => output.push_str(&format!("; source line {n}\n")),
+                None => output.push_str("; (no source mapping)\n"),
+            }
+        }
+        output.push_str(&line.text);
+        output.push('\n');
+    }
+
+    output
+}
+
 fn parse_compilers_language(s: &str) -> (&str, &str) {
     let s = s.trim();
 
@@ -197,7 +317,16 @@ fn parse_compilers_language(s: &str) -> (&str, &str) {
     }
 }
 
-fn parse_compile_msg(msg: &Message) -> Result<(String, String), String> {
+/// A parsed `/compile` invocation: the target compiler, whether `--annotate`
+/// was requested, any remaining user arguments, and the submitted code.
+struct ParsedCompile {
+    compiler_id: String,
+    annotate: bool,
+    user_arguments: String,
+    code: String,
+}
+
+fn parse_compile_msg(msg: &Message) -> Result<ParsedCompile, String> {
     let parsed_entities = msg.parse_entities().unwrap_or_default();
     let code_block = parsed_entities
         .iter()
@@ -220,19 +349,89 @@ fn parse_compile_msg(msg: &Message) -> Result<(String, String), String> {
     let compiler_commands = compile_full_command
         .split_whitespace()
         .collect::<Vec<&str>>();
-    let compiler_id = match compiler_commands[..] {
-        [_command, compiler_id, ..] => Some(compiler_id),
+    let compiler_id_and_flags = match compiler_commands[..] {
+        [_command, compiler_id, flags @ ..] => Some((compiler_id, flags)),
         [..] => None,
     };
 
-    if let Some(id) = compiler_id {
-        return Ok((id.to_string(), code.to_string()));
+    if let Some((id, flags)) = compiler_id_and_flags {
+        let annotate = flags.contains(&"--annotate");
+        let user_arguments = flags
+            .iter()
+            .filter(|flag| **flag != "--annotate")
+            .copied()
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        return Ok(ParsedCompile {
+            compiler_id: id.to_string(),
+            annotate,
+            user_arguments,
+            code: code.to_string(),
+        });
     } else {
         return Err("Invalid format. Expected compile command.".to_string());
     }
 }
 
-async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
+/// Parses a `/diff` message into the list of compiler ids to compare (at
+/// least two) and the submitted code.
+fn parse_diff_msg(msg: &Message) -> Result<(Vec<String>, String), String> {
+    let parsed_entities = msg.parse_entities().unwrap_or_default();
+    let code_block = parsed_entities
+        .iter()
+        .filter_map(|entity| match entity.kind() {
+            MessageEntityKind::Pre { .. } | MessageEntityKind::Code => {
+                Some(entity.text().to_string())
+            }
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+    let code_block_len = code_block.len();
+    if code_block_len != 1 {
+        let error_text =
+            format!("Invalid format. Expected exactly one code block, got {code_block_len}.");
+        return Err(error_text);
+    }
+    let code = code_block.first().unwrap();
+    let text = msg.text().unwrap_or_default();
+    let diff_full_command = text.replace(code, "");
+    let tokens = diff_full_command.split_whitespace().collect::<Vec<&str>>();
+
+    let compiler_ids = match tokens[..] {
+        [_command, ids @ ..] if ids.len() >= 2 => {
+            ids.iter().map(|id| id.to_string()).collect::<Vec<String>>()
+        }
+        [_command, ..] => {
+            return Err("Invalid format. Expected at least two compiler ids.".to_string())
+        }
+        [..] => return Err("Invalid format. Expected diff command.".to_string()),
+    };
+
+    Ok((compiler_ids, code.to_string()))
+}
+
+/// Renders a per-compiler diagnostics/assembly result as plain assembly, or
+/// an `Err` describing why that compiler couldn't produce any.
+fn diff_target_output(output: godbolt::CompilationOutput) -> Result<String, String> {
+    match output {
+        godbolt::CompilationOutput::Assembly(lines) => Ok(lines
+            .into_iter()
+            .map(|line| line.text)
+            .collect::<Vec<String>>()
+            .join("\n")),
+        godbolt::CompilationOutput::Diagnostics(diags) => {
+            let messages = diags
+                .into_iter()
+                .map(|diag| diag.message)
+                .collect::<Vec<String>>()
+                .join("; ");
+            Err(format!("compilation failed: {messages}"))
+        }
+    }
+}
+
+async fn answer(bot: Bot, msg: Message, cmd: Command, chat_flags: ChatFlags) -> ResponseResult<()> {
     match cmd {
         Command::Help => {
             bot.send_message(msg.chat.id, Command::descriptions().to_string())
@@ -249,17 +448,40 @@ async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
         Command::Compile => {
             let parse_result = parse_compile_msg(&msg);
             match parse_result {
-                Ok((id, code)) => {
-                    let res = godbolt::compile(&id, &code).await?;
+                Ok(parsed) => {
+                    let user_arguments = if parsed.user_arguments.is_empty() {
+                        chat_flags
+                            .lock()
+                            .unwrap()
+                            .get(&msg.chat.id)
+                            .cloned()
+                            .unwrap_or_default()
+                    } else {
+                        parsed.user_arguments
+                    };
+                    let options = godbolt::CompileOptions {
+                        user_arguments,
+                        ..Default::default()
+                    };
+                    let res = godbolt::compile(&parsed.compiler_id, &parsed.code, &options).await?;
                     match res {
-                        godbolt::CompilationOutput::Assembly(assembly) => {
-                            log::info!("Assembly: {assembly}");
-                            send_md(&bot, msg.chat.id, &wrap_in_md(&assembly)).await?;
+                        godbolt::CompilationOutput::Assembly(lines) => {
+                            log::info!("Assembly: {lines:?}");
+                            let rendered = if parsed.annotate {
+                                format_annotated_assembly(&lines)
+                            } else {
+                                lines
+                                    .iter()
+                                    .map(|line| line.text.as_str())
+                                    .collect::<Vec<&str>>()
+                                    .join("\n")
+                            };
+                            send_md(&bot, msg.chat.id, &wrap_in_md(&rendered)).await?;
                         }
-                        godbolt::CompilationOutput::Stderr(raw_err) => {
-                            log::info!("Error: {raw_err}");
-                            let err = strip_ansi_escapes::strip_str(&raw_err);
-                            send_md(&bot, msg.chat.id, &wrap_in_md(&err)).await?;
+                        godbolt::CompilationOutput::Diagnostics(diags) => {
+                            log::info!("Diagnostics: {diags:?}");
+                            let report = format_diagnostics(&diags, &parsed.code);
+                            send_md(&bot, msg.chat.id, &report).await?;
                         }
                     }
                 }
@@ -278,6 +500,81 @@ async fn answer(bot: Bot, msg: Message, cmd: Command) -> ResponseResult<()> {
             let message = format_compilers(&filtered_compilers);
             send_md(&bot, msg.chat.id, &message).await?;
         }
+        Command::SetFlags { flags } => {
+            chat_flags.lock().unwrap().insert(msg.chat.id, flags);
+            send_message(&bot, msg.chat.id, "Default flags updated for this chat.").await?;
+        }
+        Command::Diff => match parse_diff_msg(&msg) {
+            Ok((compiler_ids, code)) => {
+                let options = godbolt::CompileOptions::default();
+                let results = join_all(
+                    compiler_ids
+                        .iter()
+                        .map(|id| godbolt::compile(id, &code, &options)),
+                )
+                .await;
+
+                let outputs: Vec<(String, Result<String, String>)> = compiler_ids
+                    .into_iter()
+                    .zip(results)
+                    .map(|(id, result)| {
+                        let output = match result {
+                            Ok(output) => diff_target_output(output),
+                            Err(e) => Err(format!("request failed: {e}")),
+                        };
+                        (id, output)
+                    })
+                    .collect();
+
+                // Diff against the first compiler that actually produced assembly,
+                // rather than always `outputs[0]` — otherwise one failing compiler
+                // would cascade its error onto every other, unrelated pair.
+                let baseline_index = outputs.iter().position(|(_, output)| output.is_ok());
+
+                let sections = match baseline_index {
+                    Some(baseline_index) => {
+                        let (baseline_id, baseline_asm) = match &outputs[baseline_index] {
+                            (id, Ok(asm)) => (id, asm),
+                            (_, Err(_)) => unreachable!("baseline_index only points at an Ok output"),
+                        };
+                        outputs
+                            .iter()
+                            .enumerate()
+                            .filter(|(index, _)| *index != baseline_index)
+                            .map(|(_, (id, output))| match output {
+                                Ok(other_asm) => {
+                                    let diff = diff::line_diff(baseline_asm, other_asm).join("\n");
+                                    format!("--- {baseline_id}\n+++ {id}\n{diff}")
+                                }
+                                Err(e) => format!("{id}: {e}"),
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n\n")
+                    }
+                    // Every compiler failed; there's no baseline to diff against, so
+                    // just report each failure independently.
+                    None => outputs
+                        .iter()
+                        .filter_map(|(id, output)| output.as_ref().err().map(|e| format!("{id}: {e}")))
+                        .collect::<Vec<String>>()
+                        .join("\n\n"),
+                };
+
+                send_md(&bot, msg.chat.id, &wrap_in_diff_md(&sections)).await?;
+            }
+            Err(error) => {
+                send_message(&bot, msg.chat.id, &error).await?;
+            }
+        },
+        Command::Ask { prompt } => match assistant::ask(&prompt).await {
+            Ok(answer) => {
+                send_message(&bot, msg.chat.id, &answer).await?;
+            }
+            Err(error) => {
+                log::info!("Assistant error: {error}");
+                send_message(&bot, msg.chat.id, &format!("Assistant error: {error}")).await?;
+            }
+        },
     };
 
     Ok(())
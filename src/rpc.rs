@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+use crate::godbolt;
+
+/// JSON-RPC 2.0 request, framed the same way the LSP/DAP ecosystem does: a
+/// `Content-Length: N\r\n\r\n` header followed by an N-byte UTF-8 JSON body.
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    #[allow(dead_code)]
+    jsonrpc: String,
+    id: Option<Value>,
+    method: String,
+    params: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcResponse {
+    jsonrpc: &'static str,
+    id: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcError {
+    code: i32,
+    message: String,
+}
+
+const PARSE_ERROR: i32 = -32700;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+
+#[derive(Debug, Deserialize)]
+struct CompileParams {
+    compiler_id: String,
+    code: String,
+    #[serde(default)]
+    options: godbolt::CompileOptions,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecuteParams {
+    compiler_id: String,
+    code: String,
+    #[serde(default)]
+    stdin: String,
+    #[serde(default)]
+    options: godbolt::CompileOptions,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListCompilersParams {
+    language_id: String,
+}
+
+async fn dispatch(method: &str, params: Option<Value>) -> Result<Value, JsonRpcError> {
+    let params = params.unwrap_or(Value::Null);
+
+    match method {
+        "compile" => {
+            let p: CompileParams = serde_json::from_value(params)
+                .map_err(|e| invalid_params(&e))?;
+            let output = godbolt::compile(&p.compiler_id, &p.code, &p.options)
+                .await
+                .map_err(|e| internal_error(&e))?;
+            serde_json::to_value(output).map_err(|e| internal_error(&e))
+        }
+        "execute" => {
+            let p: ExecuteParams = serde_json::from_value(params)
+                .map_err(|e| invalid_params(&e))?;
+            let output = godbolt::execute(&p.compiler_id, &p.code, &p.stdin, &p.options)
+                .await
+                .map_err(|e| internal_error(&e))?;
+            serde_json::to_value(output).map_err(|e| internal_error(&e))
+        }
+        "listLanguages" => {
+            let langs = godbolt::languages().await.map_err(|e| internal_error(&e))?;
+            serde_json::to_value(langs).map_err(|e| internal_error(&e))
+        }
+        "listCompilers" => {
+            let p: ListCompilersParams = serde_json::from_value(params)
+                .map_err(|e| invalid_params(&e))?;
+            let compilers = godbolt::compilers_for_language(&p.language_id)
+                .await
+                .map_err(|e| internal_error(&e))?;
+            serde_json::to_value(compilers).map_err(|e| internal_error(&e))
+        }
+        other => Err(JsonRpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("method '{other}' not found"),
+        }),
+    }
+}
+
+fn invalid_params(e: &impl std::fmt::Display) -> JsonRpcError {
+    JsonRpcError {
+        code: INVALID_PARAMS,
+        message: format!("invalid params: {e}"),
+    }
+}
+
+fn internal_error(e: &impl std::fmt::Display) -> JsonRpcError {
+    JsonRpcError {
+        code: INTERNAL_ERROR,
+        message: e.to_string(),
+    }
+}
+
+/// Parses and dispatches one JSON-RPC request body, returning `None` for
+/// notifications (requests without an `id`), which get no response.
+async fn handle_message(body: &str) -> Option<String> {
+    let request: JsonRpcRequest = match serde_json::from_str(body) {
+        Ok(req) => req,
+        Err(e) => {
+            let response = JsonRpcResponse {
+                jsonrpc: "2.0",
+                id: None,
+                result: None,
+                error: Some(JsonRpcError {
+                    code: PARSE_ERROR,
+                    message: format!("failed to parse request: {e}"),
+                }),
+            };
+            return Some(serde_json::to_string(&response).unwrap_or_default());
+        }
+    };
+
+    let id = request.id.clone();
+    let (result, error) = match dispatch(&request.method, request.params).await {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    id.as_ref()?;
+
+    let response = JsonRpcResponse {
+        jsonrpc: "2.0",
+        id,
+        result,
+        error,
+    };
+    Some(serde_json::to_string(&response).unwrap_or_default())
+}
+
+async fn read_message<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> std::io::Result<Option<String>> {
+    let mut content_length: Option<usize> = None;
+
+    loop {
+        let mut header_line = String::new();
+        let bytes_read = reader.read_line(&mut header_line).await?;
+        if bytes_read == 0 {
+            return Ok(None);
+        }
+
+        let header_line = header_line.trim_end_matches(['\r', '\n']);
+        if header_line.is_empty() {
+            break;
+        }
+
+        if let Some(value) = header_line.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let content_length = match content_length {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+
+    let mut body = vec![0u8; content_length];
+    tokio::io::AsyncReadExt::read_exact(reader, &mut body).await?;
+
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, body: &str) -> std::io::Result<()> {
+    let header = format!("Content-Length: {}\r\n\r\n", body.len());
+    writer.write_all(header.as_bytes()).await?;
+    writer.write_all(body.as_bytes()).await?;
+    writer.flush().await
+}
+
+async fn serve_connection<R, W>(reader: R, writer: W)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let mut reader = BufReader::new(reader);
+    let writer = Arc::new(Mutex::new(writer));
+
+    loop {
+        match read_message(&mut reader).await {
+            Ok(Some(body)) => {
+                let writer = writer.clone();
+                tokio::spawn(async move {
+                    if let Some(response) = handle_message(&body).await {
+                        let mut writer = writer.lock().await;
+                        if let Err(e) = write_message(&mut *writer, &response).await {
+                            log::error!("Failed to write JSON-RPC response: {e}");
+                        }
+                    }
+                });
+            }
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("Failed to read JSON-RPC message: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// Serves the JSON-RPC API over stdio, one request per in-flight task so
+/// concurrent calls on the same connection are multiplexed by their `id`.
+pub async fn serve_stdio() {
+    serve_connection(tokio::io::stdin(), tokio::io::stdout()).await;
+}
+
+/// Serves the JSON-RPC API over TCP, accepting any number of concurrent
+/// connections, each multiplexing its own requests as `serve_stdio` does.
+pub async fn serve_tcp(addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("JSON-RPC server listening on {addr}");
+
+    loop {
+        let (socket, peer) = listener.accept().await?;
+        log::info!("Accepted JSON-RPC connection from {peer}");
+        let (read_half, write_half) = tokio::io::split(socket);
+        tokio::spawn(serve_connection(read_half, write_half));
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::godbolt;
+use crate::llm::ToolDefinition;
+
+/// The tool definitions advertised to the model, one per `godbolt::*`
+/// function it is allowed to call.
+pub fn tool_definitions() -> Vec<ToolDefinition> {
+    vec![
+        ToolDefinition::new(
+            "compile",
+            "Compile a source snippet with a given compiler and return its assembly or diagnostics.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "compiler_id": { "type": "string", "description": "Compiler Explorer compiler id, e.g. 'g122' for GCC 12.2." },
+                    "code": { "type": "string", "description": "The source code to compile." },
+                    "user_arguments": { "type": "string", "description": "Extra compiler flags, e.g. '-O3 -std=c++20'." },
+                },
+                "required": ["compiler_id", "code"],
+            }),
+        ),
+        ToolDefinition::new(
+            "execute",
+            "Compile and run a source snippet with a given compiler, feeding it the provided stdin.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "compiler_id": { "type": "string", "description": "Compiler Explorer compiler id, e.g. 'g122' for GCC 12.2." },
+                    "code": { "type": "string", "description": "The source code to compile and run." },
+                    "stdin": { "type": "string", "description": "Standard input to feed the program." },
+                    "user_arguments": { "type": "string", "description": "Extra compiler flags, e.g. '-O3 -std=c++20'." },
+                },
+                "required": ["compiler_id", "code"],
+            }),
+        ),
+        ToolDefinition::new(
+            "compilers_for_language",
+            "List the compilers Compiler Explorer supports for a given language id.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "language_id": { "type": "string", "description": "Language id, e.g. 'cpp', 'rust', 'csharp'." },
+                },
+                "required": ["language_id"],
+            }),
+        ),
+        ToolDefinition::new(
+            "languages",
+            "List every language Compiler Explorer supports.",
+            json!({ "type": "object", "properties": {} }),
+        ),
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+struct CompileArgs {
+    compiler_id: String,
+    code: String,
+    #[serde(default)]
+    user_arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecuteArgs {
+    compiler_id: String,
+    code: String,
+    #[serde(default)]
+    stdin: String,
+    #[serde(default)]
+    user_arguments: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompilersForLanguageArgs {
+    language_id: String,
+}
+
+#[derive(Debug)]
+pub enum ToolError {
+    UnknownTool(String),
+    InvalidArguments(serde_json::Error),
+    Godbolt(reqwest::Error),
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::UnknownTool(name) => write!(f, "unknown tool '{name}'"),
+            ToolError::InvalidArguments(e) => write!(f, "invalid tool arguments: {e}"),
+            ToolError::Godbolt(e) => write!(f, "godbolt request failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+/// Memoizes tool calls for the lifetime of a single `/ask` conversation, so
+/// that repeated `languages`/`compilers_for_language` lookups made across
+/// tool-calling steps reuse the first result instead of hitting the API again.
+#[derive(Default)]
+pub struct ToolCache {
+    results: HashMap<(String, String), String>,
+}
+
+/// Looks up `name`/`arguments` in the cache, dispatching to the matching
+/// `godbolt::*` function and serializing its result to JSON on a miss.
+pub async fn dispatch(name: &str, arguments: &str, cache: &mut ToolCache) -> Result<String, ToolError> {
+    let key = (name.to_string(), arguments.to_string());
+    if let Some(cached) = cache.results.get(&key) {
+        return Ok(cached.clone());
+    }
+
+    let result = dispatch_uncached(name, arguments).await?;
+    cache.results.insert(key, result.clone());
+    Ok(result)
+}
+
+async fn dispatch_uncached(name: &str, arguments: &str) -> Result<String, ToolError> {
+    match name {
+        "compile" => {
+            let args: CompileArgs =
+                serde_json::from_str(arguments).map_err(ToolError::InvalidArguments)?;
+            let options = godbolt::CompileOptions {
+                user_arguments: args.user_arguments,
+                ..Default::default()
+            };
+            let output = godbolt::compile(&args.compiler_id, &args.code, &options)
+                .await
+                .map_err(ToolError::Godbolt)?;
+            Ok(serde_json::to_string(&output).unwrap_or_default())
+        }
+        "execute" => {
+            let args: ExecuteArgs =
+                serde_json::from_str(arguments).map_err(ToolError::InvalidArguments)?;
+            let options = godbolt::CompileOptions {
+                user_arguments: args.user_arguments,
+                ..Default::default()
+            };
+            let output = godbolt::execute(&args.compiler_id, &args.code, &args.stdin, &options)
+                .await
+                .map_err(ToolError::Godbolt)?;
+            Ok(serde_json::to_string(&output).unwrap_or_default())
+        }
+        "compilers_for_language" => {
+            let args: CompilersForLanguageArgs =
+                serde_json::from_str(arguments).map_err(ToolError::InvalidArguments)?;
+            let compilers = godbolt::compilers_for_language(&args.language_id)
+                .await
+                .map_err(ToolError::Godbolt)?;
+            Ok(serde_json::to_string(&compilers).unwrap_or_default())
+        }
+        "languages" => {
+            let langs = godbolt::languages().await.map_err(ToolError::Godbolt)?;
+            Ok(serde_json::to_string(&langs).unwrap_or_default())
+        }
+        other => Err(ToolError::UnknownTool(other.to_string())),
+    }
+}